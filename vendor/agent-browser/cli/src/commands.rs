@@ -111,6 +111,19 @@ pub fn parse_command(args: &[String], flags: &Flags) -> Option<Value> {
                             i += 1;
                         }
                     }
+                    "--diff" => {
+                        if let Some(baseline) = rest.get(i + 1) {
+                            obj.insert("diff".to_string(), json!(true));
+                            obj.insert("baseline".to_string(), json!(baseline));
+                            i += 1;
+                        }
+                    }
+                    "--save" => {
+                        if let Some(baseline) = rest.get(i + 1) {
+                            obj.insert("save".to_string(), json!(baseline));
+                            i += 1;
+                        }
+                    }
                     _ => {}
                 }
                 i += 1;
@@ -121,6 +134,9 @@ pub fn parse_command(args: &[String], flags: &Flags) -> Option<Value> {
         // === Eval ===
         "eval" => Some(json!({ "id": id, "action": "evaluate", "script": rest.join(" ") })),
 
+        // === Batch ===
+        "batch" => parse_batch(&rest, &id, flags, 0),
+
         // === Close ===
         "close" | "quit" | "exit" => Some(json!({ "id": id, "action": "close" })),
 
@@ -200,6 +216,23 @@ pub fn parse_command(args: &[String], flags: &Flags) -> Option<Value> {
                 let value = rest.get(3);
                 Some(json!({ "id": id, "action": "storage", "storageType": storage_type, "operation": op, "key": key, "value": value }))
             }
+            Some("indexeddb") => {
+                let op = rest.get(1).unwrap_or(&"list");
+                let database = rest.get(2);
+                let store = rest.get(3);
+                let key = rest.get(4);
+                let value = rest.get(5);
+                Some(json!({
+                    "id": id,
+                    "action": "storage",
+                    "storageType": "indexeddb",
+                    "operation": op,
+                    "database": database,
+                    "store": store,
+                    "key": key,
+                    "value": value
+                }))
+            }
             _ => None,
         },
 
@@ -269,8 +302,8 @@ pub fn parse_command(args: &[String], flags: &Flags) -> Option<Value> {
 
         // === State ===
         "state" => match rest.get(0).map(|s| *s) {
-            Some("save") => Some(json!({ "id": id, "action": "state_save", "path": rest.get(1)? })),
-            Some("load") => Some(json!({ "id": id, "action": "state_load", "path": rest.get(1)? })),
+            Some("save") => parse_state(&rest, "state_save", &id),
+            Some("load") => parse_state(&rest, "state_load", &id),
             _ => None,
         },
 
@@ -278,6 +311,93 @@ pub fn parse_command(args: &[String], flags: &Flags) -> Option<Value> {
     }
 }
 
+/// Batch files may reference other batch files; cap the nesting so a cycle
+/// (or just a deep chain) can't recurse the process into a stack overflow.
+const MAX_BATCH_DEPTH: u32 = 8;
+
+fn parse_batch(rest: &[&str], id: &str, flags: &Flags, depth: u32) -> Option<Value> {
+    let path = rest.get(0)?;
+    let stop_on_error = rest.iter().any(|&s| s == "--stop-on-error");
+
+    if depth >= MAX_BATCH_DEPTH {
+        return Some(json!({
+            "id": id,
+            "action": "error",
+            "message": format!("batch nesting exceeded max depth of {}", MAX_BATCH_DEPTH)
+        }));
+    }
+
+    let contents = std::fs::read_to_string(path).ok()?;
+    let steps: Vec<Value> = contents
+        .lines()
+        .enumerate()
+        .map(|(line_no, line)| (line_no + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .map(|(i, (line_no, line))| {
+            let step_id = format!("{}-{}", id, i);
+            let step_args = tokenize_batch_line(line);
+            let parsed = if step_args.first().map(String::as_str) == Some("batch") {
+                let nested_rest: Vec<&str> = step_args[1..].iter().map(String::as_str).collect();
+                parse_batch(&nested_rest, &step_id, flags, depth + 1)
+            } else {
+                parse_command(&step_args, flags)
+            };
+            match parsed {
+                Some(mut step) => {
+                    if let Some(obj) = step.as_object_mut() {
+                        obj.insert("id".to_string(), json!(step_id));
+                    }
+                    step
+                }
+                None => json!({
+                    "id": step_id,
+                    "action": "error",
+                    "line": line_no,
+                    "command": line,
+                    "message": "unrecognized command"
+                }),
+            }
+        })
+        .collect();
+    Some(json!({ "id": id, "action": "batch", "steps": steps, "stopOnError": stop_on_error }))
+}
+
+/// Splits a batch file line into args, honoring single/double quotes so
+/// selectors and names containing spaces (`find role button --name "Submit Order"`)
+/// survive intact instead of being torn apart by whitespace splitting.
+fn tokenize_batch_line(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut in_token = false;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_token = true;
+            }
+            None if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    tokens
+}
+
 fn parse_find(rest: &[&str], id: &str) -> Option<Value> {
     let locator = rest.get(0)?;
     let value = rest.get(1)?;
@@ -317,6 +437,25 @@ fn parse_find(rest: &[&str], id: &str) -> Option<Value> {
     }
 }
 
+fn parse_state(rest: &[&str], action: &str, id: &str) -> Option<Value> {
+    let path = rest.get(1)?;
+    let remote = path.starts_with("http://") || path.starts_with("https://") || path.starts_with("dav://");
+
+    let user_idx = rest.iter().position(|&s| s == "--user");
+    let user = user_idx.and_then(|i| rest.get(i + 1).map(|s| *s));
+    let pass_idx = rest.iter().position(|&s| s == "--pass");
+    let pass = pass_idx.and_then(|i| rest.get(i + 1).map(|s| *s));
+
+    Some(json!({
+        "id": id,
+        "action": action,
+        "path": path,
+        "remote": remote,
+        "username": user,
+        "password": pass
+    }))
+}
+
 fn parse_set(rest: &[&str], id: &str) -> Option<Value> {
     match rest.get(0).map(|s| *s) {
         Some("viewport") => {
@@ -341,6 +480,27 @@ fn parse_set(rest: &[&str], id: &str) -> Option<Value> {
         Some("credentials") | Some("auth") => {
             Some(json!({ "id": id, "action": "credentials", "username": rest.get(1)?, "password": rest.get(2)? }))
         }
+        Some("proxy") => {
+            if rest.iter().any(|&s| s == "--off") {
+                return Some(json!({ "id": id, "action": "proxy", "enabled": false }));
+            }
+            let server = rest.get(1)?;
+            let bypass_idx = rest.iter().position(|&s| s == "--bypass");
+            let bypass = bypass_idx.and_then(|i| rest.get(i + 1).map(|s| *s));
+            let user_idx = rest.iter().position(|&s| s == "--user");
+            let user = user_idx.and_then(|i| rest.get(i + 1).map(|s| *s));
+            let pass_idx = rest.iter().position(|&s| s == "--pass");
+            let pass = pass_idx.and_then(|i| rest.get(i + 1).map(|s| *s));
+            Some(json!({
+                "id": id,
+                "action": "proxy",
+                "enabled": true,
+                "server": server,
+                "bypass": bypass,
+                "username": user,
+                "password": pass
+            }))
+        }
         Some("media") => {
             let color = if rest.iter().any(|&s| s == "dark") {
                 "dark"
@@ -355,3 +515,233 @@ fn parse_set(rest: &[&str], id: &str) -> Option<Value> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_flags() -> Flags {
+        Flags {
+            json: false,
+            full: false,
+            headed: false,
+            debug: false,
+            session: "default".to_string(),
+        }
+    }
+
+    fn args(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn set_proxy_with_bypass_and_credentials() {
+        let result = parse_command(
+            &args(&[
+                "set", "proxy", "http://proxy:8080", "--bypass", "localhost,127.0.0.1", "--user",
+                "u", "--pass", "p",
+            ]),
+            &test_flags(),
+        )
+        .unwrap();
+        assert_eq!(result["action"], "proxy");
+        assert_eq!(result["enabled"], true);
+        assert_eq!(result["server"], "http://proxy:8080");
+        assert_eq!(result["bypass"], "localhost,127.0.0.1");
+        assert_eq!(result["username"], "u");
+        assert_eq!(result["password"], "p");
+    }
+
+    #[test]
+    fn set_proxy_off_disables_regardless_of_server() {
+        let result = parse_command(
+            &args(&["set", "proxy", "http://x:1", "--off"]),
+            &test_flags(),
+        )
+        .unwrap();
+        assert_eq!(result["action"], "proxy");
+        assert_eq!(result["enabled"], false);
+    }
+
+    #[test]
+    fn storage_indexeddb_list_defaults_to_database_only() {
+        let result =
+            parse_command(&args(&["storage", "indexeddb", "list", "mydb"]), &test_flags())
+                .unwrap();
+        assert_eq!(result["action"], "storage");
+        assert_eq!(result["storageType"], "indexeddb");
+        assert_eq!(result["operation"], "list");
+        assert_eq!(result["database"], "mydb");
+    }
+
+    #[test]
+    fn storage_indexeddb_get_set_delete() {
+        let get = parse_command(
+            &args(&["storage", "indexeddb", "get", "mydb", "store1", "key1"]),
+            &test_flags(),
+        )
+        .unwrap();
+        assert_eq!(get["operation"], "get");
+        assert_eq!(get["store"], "store1");
+        assert_eq!(get["key"], "key1");
+
+        let set = parse_command(
+            &args(&[
+                "storage", "indexeddb", "set", "mydb", "store1", "key1", "value1",
+            ]),
+            &test_flags(),
+        )
+        .unwrap();
+        assert_eq!(set["operation"], "set");
+        assert_eq!(set["value"], "value1");
+
+        let delete = parse_command(
+            &args(&["storage", "indexeddb", "delete", "mydb", "store1", "key1"]),
+            &test_flags(),
+        )
+        .unwrap();
+        assert_eq!(delete["operation"], "delete");
+    }
+
+    #[test]
+    fn snapshot_diff_references_a_baseline() {
+        let result = parse_command(&args(&["snapshot", "--diff", "baseline1"]), &test_flags())
+            .unwrap();
+        assert_eq!(result["action"], "snapshot");
+        assert_eq!(result["diff"], true);
+        assert_eq!(result["baseline"], "baseline1");
+    }
+
+    #[test]
+    fn snapshot_save_tags_a_baseline() {
+        let result = parse_command(&args(&["snapshot", "--save", "baseline1"]), &test_flags())
+            .unwrap();
+        assert_eq!(result["action"], "snapshot");
+        assert_eq!(result["save"], "baseline1");
+        assert!(result.get("diff").is_none());
+    }
+
+    #[test]
+    fn state_save_to_webdav_url_with_credentials() {
+        let result = parse_command(
+            &args(&[
+                "state",
+                "save",
+                "https://dav.example.com/state.json",
+                "--user",
+                "u",
+                "--pass",
+                "p",
+            ]),
+            &test_flags(),
+        )
+        .unwrap();
+        assert_eq!(result["action"], "state_save");
+        assert_eq!(result["remote"], true);
+        assert_eq!(result["username"], "u");
+        assert_eq!(result["password"], "p");
+    }
+
+    #[test]
+    fn state_load_local_path_is_not_remote() {
+        let result = parse_command(
+            &args(&["state", "load", "/tmp/state.json"]),
+            &test_flags(),
+        )
+        .unwrap();
+        assert_eq!(result["action"], "state_load");
+        assert_eq!(result["remote"], false);
+        assert!(result["username"].is_null());
+    }
+
+    fn write_temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("agent_browser_test_{}_{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn tokenize_batch_line_preserves_quoted_spaces() {
+        let tokens = tokenize_batch_line(r#"find role button --name "Submit Order" --exact"#);
+        assert_eq!(
+            tokens,
+            vec!["find", "role", "button", "--name", "Submit Order", "--exact"]
+        );
+    }
+
+    #[test]
+    fn tokenize_batch_line_flushes_empty_quoted_arg() {
+        let tokens = tokenize_batch_line(r#"find role button --name "" --exact"#);
+        assert_eq!(
+            tokens,
+            vec!["find", "role", "button", "--name", "", "--exact"]
+        );
+    }
+
+    #[test]
+    fn batch_assigns_per_step_ids_and_parses_each_line() {
+        let path = write_temp_file("basic.txt", "click #a\nfill #b hello\n");
+        let result =
+            parse_command(&args(&["batch", path.to_str().unwrap()]), &test_flags()).unwrap();
+        let steps = result["steps"].as_array().unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0]["action"], "click");
+        assert_eq!(steps[1]["action"], "fill");
+        assert_ne!(steps[0]["id"], steps[1]["id"]);
+        assert!(steps[1]["id"].as_str().unwrap().ends_with("-1"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn batch_emits_error_step_for_unparseable_line_instead_of_dropping_it() {
+        let path = write_temp_file("bad.txt", "click #a\nnotarealcommand\n");
+        let result =
+            parse_command(&args(&["batch", path.to_str().unwrap()]), &test_flags()).unwrap();
+        let steps = result["steps"].as_array().unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[1]["action"], "error");
+        assert_eq!(steps[1]["line"], 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn batch_resolves_a_nested_batch_file() {
+        let inner = write_temp_file("inner.txt", "click #inner\n");
+        let outer = write_temp_file(
+            "outer.txt",
+            &format!("click #outer\nbatch {}\n", inner.to_str().unwrap()),
+        );
+        let result =
+            parse_command(&args(&["batch", outer.to_str().unwrap()]), &test_flags()).unwrap();
+        let steps = result["steps"].as_array().unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[1]["action"], "batch");
+        let nested_steps = steps[1]["steps"].as_array().unwrap();
+        assert_eq!(nested_steps.len(), 1);
+        assert_eq!(nested_steps[0]["action"], "click");
+        std::fs::remove_file(&inner).unwrap();
+        std::fs::remove_file(&outer).unwrap();
+    }
+
+    #[test]
+    fn batch_self_reference_stops_at_max_depth_without_overflow() {
+        let path = write_temp_file("cycle.txt", "");
+        let content = format!("batch {}\n", path.to_str().unwrap());
+        std::fs::write(&path, &content).unwrap();
+
+        let result =
+            parse_command(&args(&["batch", path.to_str().unwrap()]), &test_flags()).unwrap();
+
+        let mut node = &result;
+        for _ in 0..MAX_BATCH_DEPTH {
+            let steps = node["steps"].as_array().unwrap();
+            assert_eq!(steps.len(), 1);
+            node = &steps[0];
+        }
+        assert_eq!(node["action"], "error");
+        assert!(node["message"].as_str().unwrap().contains("max depth"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}